@@ -1,6 +1,19 @@
 use rand::Rng;
 
-#[derive(PartialEq)]
+mod nn;
+mod raws;
+
+const DEFAULT_WIDTH: u32 = 40;
+const DEFAULT_HEIGHT: u32 = 20;
+
+const MCTS_ITERATIONS: u32 = 500;
+const MCTS_MAX_ROLLOUT_STEPS: u32 = 60;
+const MCTS_EXPLORATION: f64 = std::f64::consts::SQRT_2;
+const MCTS_FOOD_REWARD: f64 = 10.0;
+const MCTS_STEP_PENALTY: f64 = -0.01;
+const MCTS_DEATH_PENALTY: f64 = -10.0;
+
+#[derive(PartialEq, Copy, Clone)]
 enum Movement {
     Up,
     Down,
@@ -8,13 +21,344 @@ enum Movement {
     Right
 }
 
-enum EntityType {
-    Wall,
-    Food
+// Lightweight clone of the board used by the MCTS autopilot so it can roll
+// out many hypothetical futures without touching ncurses or the real Game.
+#[derive(Clone)]
+struct SimState {
+    width: u32,
+    height: u32,
+    snake: std::collections::VecDeque<SnakePart>,
+    food: (u32, u32),
+    walls: Vec<(u32, u32)>,
+    movement: Option<Movement>,
+    alive: bool
+}
+
+struct MctsNode {
+    state: SimState,
+    movement_taken: Option<Movement>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    visits: u32,
+    total_reward: f64,
+    untried_moves: Vec<Movement>
+}
+
+fn is_reverse(current: Movement, candidate: Movement) -> bool {
+    matches!(
+        (current, candidate),
+        (Movement::Up, Movement::Down) | (Movement::Down, Movement::Up) |
+        (Movement::Left, Movement::Right) | (Movement::Right, Movement::Left)
+    )
+}
+
+// Moves that don't immediately reverse the current direction or walk the
+// head into a wall/self-collision, mirroring the rules `update` enforces.
+fn legal_moves(state: &SimState) -> Vec<Movement> {
+    let head = *state.snake.back().unwrap();
+    let mut moves = Vec::new();
+
+    for &mv in &[Movement::Up, Movement::Down, Movement::Left, Movement::Right] {
+        if let Some(current) = state.movement {
+            if is_reverse(current, mv) {
+                continue;
+            }
+        }
+
+        let (nx, ny) = match mv {
+            Movement::Up    => { if head.y == 0 { continue; } (head.x, head.y - 1) },
+            Movement::Down  => (head.x, head.y + 1),
+            Movement::Left  => { if head.x == 0 { continue; } (head.x - 1, head.y) },
+            Movement::Right => (head.x + 1, head.y)
+        };
+
+        if state.walls.iter().any(|&(wx, wy)| wx == nx && wy == ny) {
+            continue;
+        }
+
+        if state.snake.iter().skip(1).any(|p| p.x == nx && p.y == ny) {
+            continue;
+        }
+
+        moves.push(mv);
+    }
+
+    moves
+}
+
+// Steps `state` by one move, reproducing the wall/food/self-collision rules
+// from `Game::update`. Returns the reward earned by this single step.
+fn sim_apply_move(state: &mut SimState, mv: Movement, rng: &mut impl Rng) -> f64 {
+    let head = *state.snake.back().unwrap();
+    let tail = state.snake.pop_front().unwrap();
+
+    let new_head = match mv {
+        Movement::Up    => SnakePart{x: head.x, y: head.y - 1},
+        Movement::Down  => SnakePart{x: head.x, y: head.y + 1},
+        Movement::Left  => SnakePart{x: head.x - 1, y: head.y},
+        Movement::Right => SnakePart{x: head.x + 1, y: head.y}
+    };
+
+    state.snake.push_back(new_head);
+    state.movement = Some(mv);
+
+    if state.walls.iter().any(|&(wx, wy)| wx == new_head.x && wy == new_head.y) {
+        state.alive = false;
+        return MCTS_DEATH_PENALTY;
+    }
+
+    let mut reward = MCTS_STEP_PENALTY;
+    let mut grow = false;
+
+    if new_head.x == state.food.0 && new_head.y == state.food.1 {
+        state.food = (rng.gen_range(2..state.width-2), rng.gen_range(2..state.height-2));
+        grow = true;
+        reward += MCTS_FOOD_REWARD;
+    }
+
+    if grow {
+        state.snake.push_front(tail);
+    }
+
+    for part in state.snake.iter().take(state.snake.len() - 1) {
+        if part.x == new_head.x && part.y == new_head.y {
+            state.alive = false;
+            return MCTS_DEATH_PENALTY;
+        }
+    }
+
+    reward
+}
+
+// Random playout from `state` until death or the step cap, used to score a
+// freshly expanded MCTS leaf.
+fn mcts_rollout(state: &SimState, rng: &mut impl Rng) -> f64 {
+    let mut sim = state.clone();
+    let mut reward = 0.0;
+
+    for _ in 0..MCTS_MAX_ROLLOUT_STEPS {
+        if !sim.alive {
+            break;
+        }
+
+        let moves = legal_moves(&sim);
+        if moves.is_empty() {
+            reward += MCTS_DEATH_PENALTY;
+            break;
+        }
+
+        let mv = moves[rng.gen_range(0..moves.len())];
+        reward += sim_apply_move(&mut sim, mv, rng);
+    }
+
+    reward
+}
+
+fn mcts_uct(parent_visits: u32, child_visits: u32, child_reward: f64) -> f64 {
+    let mean_reward = child_reward / child_visits as f64;
+    mean_reward + MCTS_EXPLORATION * ((parent_visits as f64).ln() / child_visits as f64).sqrt()
+}
+
+fn mcts_select_child(nodes: &[MctsNode], node_idx: usize) -> usize {
+    let parent_visits = nodes[node_idx].visits;
+
+    *nodes[node_idx].children.iter().max_by(|&&a, &&b| {
+        let ucb_a = mcts_uct(parent_visits, nodes[a].visits, nodes[a].total_reward);
+        let ucb_b = mcts_uct(parent_visits, nodes[b].visits, nodes[b].total_reward);
+        ucb_a.partial_cmp(&ucb_b).unwrap()
+    }).unwrap()
+}
+
+// Runs a fixed budget of SELECT/EXPAND/SIMULATE/BACKPROPAGATE iterations
+// from `state` and returns the most-visited move out of the root.
+fn mcts_choose_move(state: &SimState) -> Movement {
+    let mut rng = rand::thread_rng();
+    let mut nodes: Vec<MctsNode> = vec![MctsNode{
+        state: state.clone(),
+        movement_taken: None,
+        parent: None,
+        children: Vec::new(),
+        visits: 0,
+        total_reward: 0.0,
+        untried_moves: legal_moves(state)
+    }];
+
+    for _ in 0..MCTS_ITERATIONS {
+        let mut node_idx = 0;
+        while nodes[node_idx].untried_moves.is_empty() && !nodes[node_idx].children.is_empty() {
+            node_idx = mcts_select_child(&nodes, node_idx);
+        }
+
+        if !nodes[node_idx].untried_moves.is_empty() {
+            let pick = rng.gen_range(0..nodes[node_idx].untried_moves.len());
+            let mv = nodes[node_idx].untried_moves.remove(pick);
+
+            let mut child_state = nodes[node_idx].state.clone();
+            sim_apply_move(&mut child_state, mv, &mut rng);
+
+            let untried_moves = if child_state.alive { legal_moves(&child_state) } else { Vec::new() };
+            let child_idx = nodes.len();
+            nodes.push(MctsNode{
+                state: child_state,
+                movement_taken: Some(mv),
+                parent: Some(node_idx),
+                children: Vec::new(),
+                visits: 0,
+                total_reward: 0.0,
+                untried_moves
+            });
+            nodes[node_idx].children.push(child_idx);
+            node_idx = child_idx;
+        }
+
+        let reward = mcts_rollout(&nodes[node_idx].state, &mut rng);
+
+        let mut cur = Some(node_idx);
+        while let Some(i) = cur {
+            nodes[i].visits += 1;
+            nodes[i].total_reward += reward;
+            cur = nodes[i].parent;
+        }
+    }
+
+    nodes[0].children.iter()
+        .max_by_key(|&&child_idx| nodes[child_idx].visits)
+        .map(|&child_idx| nodes[child_idx].movement_taken.unwrap())
+        .unwrap_or(Movement::Up)
+}
+
+const CAVE_FILL_PROBABILITY: f64 = 0.45;
+const CAVE_SMOOTHING_ITERATIONS: u32 = 5;
+const CAVE_SURVIVAL_THRESHOLD: u32 = 5;
+// A wall cell with few wall neighbors still survives smoothing as long as
+// it clears this lower bar, so sparse interior walls don't get eroded away
+// into one large open hall.
+const CAVE_SPARSE_SURVIVAL_THRESHOLD: u32 = 2;
+
+#[derive(PartialEq, Copy, Clone)]
+enum Mode {
+    Classic,
+    Cave
+}
+
+fn classic_walls(width: u32, height: u32) -> Vec<(u32, u32)> {
+    let mut walls = Vec::new();
+
+    for x in 0..width {
+        walls.push((x, 0));
+        walls.push((x, height - 1));
+    }
+    for y in 1..height - 1 {
+        walls.push((0, y));
+        walls.push((width - 1, y));
+    }
+
+    walls
+}
+
+fn count_wall_neighbors(grid: &[Vec<bool>], width: u32, height: u32, x: u32, y: u32) -> u32 {
+    let mut count = 0;
+
+    for dy in -1i64..=1 {
+        for dx in -1i64..=1 {
+            if dx == 0 && dy == 0 {
+                continue;
+            }
+
+            let nx = x as i64 + dx;
+            let ny = y as i64 + dy;
+
+            if nx < 0 || ny < 0 || nx as u32 >= width || ny as u32 >= height || grid[ny as usize][nx as usize] {
+                count += 1;
+            }
+        }
+    }
+
+    count
+}
+
+fn smooth_cave(grid: &[Vec<bool>], width: u32, height: u32) -> Vec<Vec<bool>> {
+    let mut next = grid.to_vec();
+
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            let neighbors = count_wall_neighbors(grid, width, height, x, y);
+            let was_wall = grid[y as usize][x as usize];
+            next[y as usize][x as usize] = neighbors >= CAVE_SURVIVAL_THRESHOLD
+                || (was_wall && neighbors >= CAVE_SPARSE_SURVIVAL_THRESHOLD);
+        }
+    }
+
+    next
+}
+
+// Cellular-automata cave: random interior fill, smoothed a few rounds so
+// wall cells clump into walls/halls instead of single-tile noise. The
+// outer border is always solid and every cell in `protected_spawns` (one
+// per snake) is always kept open.
+fn cave_walls(width: u32, height: u32, protected_spawns: &[(u32, u32)], rng: &mut impl Rng) -> Vec<(u32, u32)> {
+    let mut grid = vec![vec![false; width as usize]; height as usize];
+
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            grid[y as usize][x as usize] = !protected_spawns.contains(&(x, y)) && rng.gen_bool(CAVE_FILL_PROBABILITY);
+        }
+    }
+
+    for _ in 0..CAVE_SMOOTHING_ITERATIONS {
+        grid = smooth_cave(&grid, width, height);
+        for &(x, y) in protected_spawns.iter() {
+            grid[y as usize][x as usize] = false;
+        }
+    }
+
+    let mut walls = classic_walls(width, height);
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            if grid[y as usize][x as usize] {
+                walls.push((x, y));
+            }
+        }
+    }
+
+    walls
+}
+
+// Cells reachable from `start` without crossing a wall, used to discard
+// cave pockets the snake could never reach and to keep food reachable.
+fn flood_fill_reachable(walls: &[(u32, u32)], width: u32, height: u32, start: (u32, u32)) -> std::collections::HashSet<(u32, u32)> {
+    let wall_set: std::collections::HashSet<(u32, u32)> = walls.iter().cloned().collect();
+    let mut visited = std::collections::HashSet::new();
+    let mut queue = std::collections::VecDeque::new();
+
+    visited.insert(start);
+    queue.push_back(start);
+
+    while let Some((x, y)) = queue.pop_front() {
+        let neighbors = [(x.wrapping_sub(1), y), (x + 1, y), (x, y.wrapping_sub(1)), (x, y + 1)];
+
+        for &(nx, ny) in neighbors.iter() {
+            if nx >= width || ny >= height || wall_set.contains(&(nx, ny)) || visited.contains(&(nx, ny)) {
+                continue;
+            }
+
+            visited.insert((nx, ny));
+            queue.push_back((nx, ny));
+        }
+    }
+
+    visited
+}
+
+fn spawn_food(reachable: &std::collections::HashSet<(u32, u32)>, exclude: (u32, u32), rng: &mut impl Rng) -> (u32, u32) {
+    let candidates: Vec<(u32, u32)> = reachable.iter().cloned().filter(|&c| c != exclude).collect();
+    candidates[rng.gen_range(0..candidates.len())]
 }
 
+// `kind` names a key in the loaded `raws::LevelDef::entities` map, which
+// supplies this entity's glyph and contact behavior.
 struct Entity {
-    entity_type: EntityType,
+    kind: String,
     x: u32,
     y: u32
 }
@@ -25,211 +369,466 @@ struct SnakePart {
     y: u32
 }
 
+// One player's snake: its own body/direction/score, plus the glyphs that
+// tell it apart on the board.
+struct Snake {
+    body: std::collections::VecDeque<SnakePart>,
+    movement: Option<Movement>,
+    alive: bool,
+    score: i32,
+    head_glyph: char,
+    body_glyph: char
+}
+
+const STATUS_ROWS: usize = 4;
+
+// Keeps the last frame that was actually drawn (`current`) alongside the
+// frame `render` is composing (`next`), so only the cells that changed
+// between frames get sent to ncurses instead of repainting everything.
+struct DoubleBuffer {
+    current: Vec<Vec<char>>,
+    next: Vec<Vec<char>>
+}
+
+impl DoubleBuffer {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            current: vec![vec![' '; width]; height],
+            next: vec![vec![' '; width]; height]
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.next.first().map_or(0, |row| row.len())
+    }
+
+    // Blanks `next` so `render` can recompose the frame into it from
+    // scratch without carrying over stale glyphs.
+    fn clear_next(&mut self) {
+        for row in self.next.iter_mut() {
+            for c in row.iter_mut() {
+                *c = ' ';
+            }
+        }
+    }
+
+    // Diffs `next` against the previously presented `current`, redraws only
+    // the changed cells, then swaps the two buffers for the next frame.
+    fn present(&mut self) {
+        for y in 0..self.next.len() {
+            for x in 0..self.next[y].len() {
+                if self.next[y][x] != self.current[y][x] {
+                    ncurses::mvaddch(y as i32, x as i32, self.next[y][x] as ncurses::chtype);
+                }
+            }
+        }
+
+        std::mem::swap(&mut self.current, &mut self.next);
+    }
+}
+
 struct Game {
     is_running: bool,
-    is_alive: bool,
+    autopilot: bool,
+    nn: Option<nn::Network>,
+    mode: Mode,
     width: u32,
     height: u32,
-    movement: Option<Movement>,
     entities: Vec<Entity>,
-    snake: std::collections::VecDeque<SnakePart>
+    snakes: Vec<Snake>,
+    level: raws::LevelDef,
+    buffer: DoubleBuffer
 }
 
 impl Game {
-    fn new() -> Self {
+    fn new(level: raws::LevelDef) -> Self {
+        let width = level.width;
+        let height = level.height;
+
         let mut res = Self {
             is_running: true,
-            is_alive:   true,
-            width:      40,
-            height:     20,
-            movement:   None,
+            autopilot:  false,
+            nn:         None,
+            mode:       Mode::Classic,
+            width,
+            height,
             entities:   Vec::new(),
-            snake:      std::collections::VecDeque::new()
+            snakes:     Vec::new(),
+            level,
+            buffer:     DoubleBuffer::new(width as usize, height as usize + STATUS_ROWS)
         };
 
         res.init_field();
 
-        return res;
+        res
     }
 
     fn init_field(&mut self) {
-        self.is_alive = true;
-        self.movement = None;
         self.entities.clear();
-        self.snake.clear();
+        self.snakes.clear();
 
-        for x in 0..self.width {
-            self.entities.push(Entity{
-                entity_type: EntityType::Wall,
-                x: x,
-                y: 0
-            });
-            self.entities.push(Entity{
-                entity_type: EntityType::Wall,
-                x: x,
-                y: self.height - 1
-            });
+        let mut rng = rand::thread_rng();
+        let spawn_p1 = (self.width / 2 - 3, self.height / 2);
+        let spawn_p2 = (self.width / 2 + 3, self.height / 2);
+
+        let mut walls = match self.mode {
+            Mode::Classic => classic_walls(self.width, self.height),
+            Mode::Cave => cave_walls(self.width, self.height, &[spawn_p1, spawn_p2], &mut rng)
+        };
+        walls.extend(self.level.walls.iter().cloned());
+
+        let reachable_p1 = flood_fill_reachable(&walls, self.width, self.height, spawn_p1);
+        let reachable_p2 = flood_fill_reachable(&walls, self.width, self.height, spawn_p2);
+        let reachable: std::collections::HashSet<(u32, u32)> = reachable_p1.union(&reachable_p2).cloned().collect();
+
+        for y in 1..self.height - 1 {
+            for x in 1..self.width - 1 {
+                if !reachable.contains(&(x, y)) && !walls.contains(&(x, y)) {
+                    walls.push((x, y));
+                }
+            }
         }
-        for y in 1..self.height-1 {
-            self.entities.push(Entity{
-                entity_type: EntityType::Wall,
-                x: 0,
-                y: y
-            });
+
+        for &(x, y) in walls.iter() {
             self.entities.push(Entity{
-                entity_type: EntityType::Wall,
-                x: self.width - 1,
-                y: y
+                kind: self.level.wall_kind.clone(),
+                x,
+                y
             });
         }
 
-        let mut rng = rand::thread_rng();
+        let food = spawn_food(&reachable, spawn_p1, &mut rng);
         self.entities.push(Entity{
-            entity_type: EntityType::Food,
-            x: rng.gen_range(2..self.width-2),
-            y: rng.gen_range(2..self.height-2)
+            kind: self.level.food_kind.clone(),
+            x: food.0,
+            y: food.1
         });
 
-        self.snake.push_back(SnakePart{
-            x: self.width / 2,
-            y: self.height / 2
-        });
+        for placement in self.level.entities_placed.iter() {
+            self.entities.push(Entity{
+                kind: placement.kind.clone(),
+                x: placement.x,
+                y: placement.y
+            });
+        }
+
+        let mut body_p1 = std::collections::VecDeque::new();
+        body_p1.push_back(SnakePart{x: spawn_p1.0, y: spawn_p1.1});
+        self.snakes.push(Snake{body: body_p1, movement: None, alive: true, score: 0, head_glyph: '1', body_glyph: 'o'});
+
+        let mut body_p2 = std::collections::VecDeque::new();
+        body_p2.push_back(SnakePart{x: spawn_p2.0, y: spawn_p2.1});
+        self.snakes.push(Snake{body: body_p2, movement: None, alive: true, score: 0, head_glyph: '2', body_glyph: 'x'});
+    }
+
+    fn wall_cells(&self) -> Vec<(u32, u32)> {
+        self.entities.iter()
+            .filter(|e| self.level.entities.get(&e.kind).is_some_and(|def| def.kills))
+            .map(|e| (e.x, e.y))
+            .collect()
+    }
+
+    fn alive_count(&self) -> usize {
+        self.snakes.iter().filter(|s| s.alive).count()
     }
 
-    fn step_forward(&mut self) {
-        let head = *self.snake.back().unwrap();
-        let tail = self.snake.pop_front().unwrap();
+    fn set_movement(&mut self, snake_idx: usize, mv: Movement) {
+        let snake = &mut self.snakes[snake_idx];
 
-        match self.movement {
-            Some(Movement::Up)    => self.snake.push_back(SnakePart{x: head.x, y: head.y - 1}),
-            Some(Movement::Down)  => self.snake.push_back(SnakePart{x: head.x, y: head.y + 1}),
-            Some(Movement::Left)  => self.snake.push_back(SnakePart{x: head.x - 1, y: head.y}),
-            Some(Movement::Right) => self.snake.push_back(SnakePart{x: head.x + 1, y: head.y}),
-            None => self.snake.push_front(tail)
+        if let Some(current) = snake.movement {
+            if is_reverse(current, mv) {
+                return;
+            }
         }
+
+        snake.movement = Some(mv);
     }
 
-    pub fn render(&self) {
-        let mut field: Vec<Vec<char>> = Vec::with_capacity(self.height as usize);
-        for _ in 0..self.height {
-            field.push(vec!['.'; self.width as usize]);
+    // Cheap snapshot of the board handed to the MCTS/NN autopilot for
+    // `snake_idx`, treating every other snake's body as an extra wall.
+    fn to_sim_state(&self, snake_idx: usize) -> SimState {
+        let mut walls = self.wall_cells();
+        for (i, other) in self.snakes.iter().enumerate() {
+            if i != snake_idx {
+                walls.extend(other.body.iter().map(|p| (p.x, p.y)));
+            }
         }
 
-        for entity in self.entities.iter() {
-            let rendered: char;
+        let food = self.entities.iter()
+            .find(|e| self.level.entities.get(&e.kind).is_some_and(|def| def.grows))
+            .map(|e| (e.x, e.y))
+            .unwrap_or((0, 0));
 
-            match entity.entity_type {
-                EntityType::Wall => rendered = '#',
-                EntityType::Food => rendered = '@'
+        let snake = &self.snakes[snake_idx];
+
+        SimState {
+            width: self.width,
+            height: self.height,
+            snake: snake.body.clone(),
+            food,
+            walls,
+            movement: snake.movement,
+            alive: true
+        }
+    }
+
+    // Moves every alive snake one step simultaneously, then resolves walls,
+    // food, self/other-body collisions and head-to-head crashes (the
+    // shorter snake dies; equal lengths both die) before committing growth.
+    fn step_turn(&mut self) {
+        let mut rng = rand::thread_rng();
+
+        let planned: Vec<Option<SnakePart>> = self.snakes.iter().map(|snake| {
+            if !snake.alive {
+                return None;
+            }
+
+            let head = *snake.body.back().unwrap();
+            Some(match snake.movement {
+                Some(Movement::Up)    => SnakePart{x: head.x, y: head.y - 1},
+                Some(Movement::Down)  => SnakePart{x: head.x, y: head.y + 1},
+                Some(Movement::Left)  => SnakePart{x: head.x - 1, y: head.y},
+                Some(Movement::Right) => SnakePart{x: head.x + 1, y: head.y},
+                None => head
+            })
+        }).collect();
+
+        let mut dead = vec![false; self.snakes.len()];
+        for i in 0..self.snakes.len() {
+            for j in (i + 1)..self.snakes.len() {
+                if let (Some(hi), Some(hj)) = (planned[i], planned[j]) {
+                    if hi.x == hj.x && hi.y == hj.y {
+                        let len_i = self.snakes[i].body.len();
+                        let len_j = self.snakes[j].body.len();
+                        if len_i <= len_j { dead[i] = true; }
+                        if len_j <= len_i { dead[j] = true; }
+                    }
+                }
             }
+        }
+
+        let prior_bodies: Vec<std::collections::VecDeque<SnakePart>> = self.snakes.iter().map(|s| s.body.clone()).collect();
+        let wall_cells = self.wall_cells();
+
+        for (i, new_head) in planned.iter().enumerate() {
+            let new_head = match new_head {
+                Some(h) if !dead[i] => *h,
+                _ => continue
+            };
+
+            if wall_cells.iter().any(|&(wx, wy)| wx == new_head.x && wy == new_head.y) {
+                dead[i] = true;
+                continue;
+            }
+
+            for (j, body) in prior_bodies.iter().enumerate() {
+                let skip_tail = if j == i { 1 } else { 0 };
+                if body.iter().skip(skip_tail).any(|p| p.x == new_head.x && p.y == new_head.y) {
+                    dead[i] = true;
+                    break;
+                }
+            }
+        }
+
+        let mut grows = vec![false; self.snakes.len()];
+        let mut scores = vec![0; self.snakes.len()];
+        let mut teleport_to = vec![None; self.snakes.len()];
+
+        for entity in self.entities.iter_mut() {
+            for (i, new_head) in planned.iter().enumerate() {
+                let new_head = match new_head {
+                    Some(h) if !dead[i] => *h,
+                    _ => continue
+                };
+
+                if entity.x != new_head.x || entity.y != new_head.y {
+                    continue;
+                }
+
+                let def = match self.level.entities.get(&entity.kind) {
+                    Some(def) => def.clone(),
+                    None => continue
+                };
+
+                if def.kills {
+                    dead[i] = true;
+                    continue;
+                }
+
+                scores[i] += def.score;
+                if def.grows {
+                    grows[i] = true;
+                }
+
+                if def.consumable {
+                    let reachable = flood_fill_reachable(&wall_cells, self.width, self.height, (new_head.x, new_head.y));
+                    let new_pos = spawn_food(&reachable, (new_head.x, new_head.y), &mut rng);
+                    entity.x = new_pos.0;
+                    entity.y = new_pos.1;
+                }
 
-            field[entity.y as usize][entity.x as usize] = rendered;
+                if def.teleports {
+                    let reachable = flood_fill_reachable(&wall_cells, self.width, self.height, (new_head.x, new_head.y));
+                    teleport_to[i] = Some(spawn_food(&reachable, (new_head.x, new_head.y), &mut rng));
+                }
+            }
         }
 
-        for snake_part in self.snake.iter().take(self.snake.len() - 1) {
-            field[snake_part.y as usize][snake_part.x as usize] = '0';
+        for (i, snake) in self.snakes.iter_mut().enumerate() {
+            if !snake.alive {
+                continue;
+            }
+
+            if dead[i] {
+                snake.alive = false;
+                continue;
+            }
+
+            let mut new_head = planned[i].unwrap();
+            if let Some((tx, ty)) = teleport_to[i] {
+                new_head.x = tx;
+                new_head.y = ty;
+            }
+
+            snake.body.push_back(new_head);
+            snake.score += scores[i];
+
+            if !grows[i] {
+                snake.body.pop_front();
+            }
         }
+    }
 
-        let head = self.snake.back().unwrap();
-        let head_char: char;
-        match self.movement {
-            Some(Movement::Up)    => head_char = '^',
-            Some(Movement::Down)  => head_char = 'v',
-            Some(Movement::Left)  => head_char = '<',
-            Some(Movement::Right) => head_char = '>',
-            None                  => head_char = '0'
+    pub fn render(&mut self) {
+        self.buffer.clear_next();
+        let total_width = self.buffer.width();
+        let end_message = if self.alive_count() <= 1 {
+            match self.snakes.iter().position(|s| s.alive) {
+                Some(winner) => Some(format!("Player {} wins! Press 'R' to restart", winner + 1)),
+                None => Some("Draw! Press 'R' to restart".to_string())
+            }
+        } else {
+            None
         };
-        field[head.y as usize][head.x as usize] = head_char;
 
-        let mut buffer = String::new();
-        for row in field.iter() {
-            for c in row.iter() {
-                buffer.push(*c);
+        let frame = &mut self.buffer.next;
+
+        for row in frame.iter_mut().take(self.height as usize) {
+            for c in row.iter_mut().take(self.width as usize) {
+                *c = '.';
+            }
+        }
+
+        for entity in self.entities.iter() {
+            let rendered = self.level.entities.get(&entity.kind).map_or('?', |def| def.glyph);
+            frame[entity.y as usize][entity.x as usize] = rendered;
+        }
+
+        for snake in self.snakes.iter() {
+            for part in snake.body.iter().take(snake.body.len() - 1) {
+                frame[part.y as usize][part.x as usize] = snake.body_glyph;
             }
-            buffer += "\n";
+
+            let head = snake.body.back().unwrap();
+            frame[head.y as usize][head.x as usize] = snake.head_glyph;
         }
-        ncurses::addstr(&buffer);
-        ncurses::addstr("\nScore: ");
-        ncurses::addstr(&(self.snake.len()-1).to_string());
-        ncurses::addstr("\n");
 
-        if !self.is_alive {
-            ncurses::addstr("\nYou died. Press 'R' to restart\n");
+        let mut row = self.height as usize + 1;
+        for (i, snake) in self.snakes.iter().enumerate() {
+            let status = if snake.alive { "alive" } else { "dead" };
+            let text = format!("Player {} ({}): {} [{}]", i + 1, snake.head_glyph, snake.score, status);
+            for (x, c) in text.chars().enumerate().take(total_width) {
+                frame[row][x] = c;
+            }
+            row += 1;
         }
+
+        if let Some(text) = end_message {
+            for (x, c) in text.chars().enumerate().take(total_width) {
+                frame[row][x] = c;
+            }
+        }
+
+        self.buffer.present();
     }
 
     pub fn update(&mut self) {
-        match ncurses::getch() as u8 {
+        let key = ncurses::getch();
+
+        match key {
             27 => self.is_running = false,
-            b'r' | b'R' => if !self.is_alive {
+            ncurses::KEY_UP    => self.set_movement(1, Movement::Up),
+            ncurses::KEY_DOWN  => self.set_movement(1, Movement::Down),
+            ncurses::KEY_LEFT  => self.set_movement(1, Movement::Left),
+            ncurses::KEY_RIGHT => self.set_movement(1, Movement::Right),
+            _ if (key == 'r' as i32 || key == 'R' as i32) && self.alive_count() <= 1 => {
                 self.init_field();
-            }
-            b'w' | b'W' => if self.movement != Some(Movement::Down) {
-                self.movement = Some(Movement::Up)
             },
-            b'a' | b'A' => if self.movement != Some(Movement::Right) {
-                self.movement = Some(Movement::Left)
-            },
-            b's' | b'S' => if self.movement != Some(Movement::Up) {
-                self.movement = Some(Movement::Down)
-            },
-            b'd' | b'D' => if self.movement != Some(Movement::Left) {
-                self.movement = Some(Movement::Right)
+            _ if key == 'p' as i32 || key == 'P' as i32 => self.autopilot = !self.autopilot,
+            _ if key == 'm' as i32 || key == 'M' as i32 => {
+                self.mode = match self.mode {
+                    Mode::Classic => Mode::Cave,
+                    Mode::Cave => Mode::Classic
+                };
+                self.init_field();
             },
+            _ if key == 'w' as i32 || key == 'W' as i32 => self.set_movement(0, Movement::Up),
+            _ if key == 'a' as i32 || key == 'A' as i32 => self.set_movement(0, Movement::Left),
+            _ if key == 's' as i32 || key == 'S' as i32 => self.set_movement(0, Movement::Down),
+            _ if key == 'd' as i32 || key == 'D' as i32 => self.set_movement(0, Movement::Right),
             _ => {}
         }
 
-        if !self.is_alive {
+        if self.alive_count() <= 1 {
             return;
         }
 
-        self.step_forward();
-
-        let mut rng = rand::thread_rng();
-        let mut grow = false;
-
-        let head = *self.snake.back().unwrap();
-        for entity in self.entities.iter_mut() {
-            if entity.x == head.x && entity.y == head.y {
-                match entity.entity_type {
-                    EntityType::Food => {
-                        entity.x = rng.gen_range(2..self.width-2);
-                        entity.y = rng.gen_range(2..self.height-2);
-                        grow = true;
-                    },
-                    EntityType::Wall => {
-                        self.is_alive = false;
-                        return;
-                    }
-                }
-            }
-        }
-        for part in self.snake.iter().take(self.snake.len() - 1) {
-            if part.x == head.x && part.y == head.y {
-                self.is_alive = false;
-                return;
-            }
+        if self.autopilot {
+            let state = self.to_sim_state(0);
+            let mv = match &self.nn {
+                Some(net) => net.choose_move(&state),
+                None => mcts_choose_move(&state)
+            };
+            self.snakes[0].movement = Some(mv);
         }
 
-        if grow {
-            self.snake.push_front(SnakePart{
-                x: 0,
-                y: 0
-            });
-            self.step_forward();
-        }
+        self.step_turn();
     }
 }
 
 fn main() {
-    let mut game = Game::new();
+    let args: Vec<String> = std::env::args().collect();
+
+    if let Some(pos) = args.iter().position(|a| a == "--train") {
+        let generations = args.get(pos + 1)
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(nn::DEFAULT_GENERATIONS);
+
+        nn::train(generations, nn::DEFAULT_WEIGHTS_PATH);
+        return;
+    }
+
+    let level_path = args.iter().position(|a| a == "--level").and_then(|pos| args.get(pos + 1));
+    let level = raws::load_level_or_default(level_path.map(|s| s.as_str()));
+
+    let mut game = Game::new(level);
+
+    if let Some(pos) = args.iter().position(|a| a == "--play") {
+        match args.get(pos + 1).map(|path| nn::Network::load(path)) {
+            Some(Ok(net)) => {
+                game.nn = Some(net);
+                game.autopilot = true;
+            },
+            Some(Err(err)) => eprintln!("failed to load network weights: {}", err),
+            None => eprintln!("--play requires a path to a weights file")
+        }
+    }
 
     let win = ncurses::initscr();
     ncurses::nodelay(win, true);
+    ncurses::keypad(win, true);
 
     while game.is_running {
-        ncurses::clear();
-
         game.update();
         game.render();
 
@@ -240,3 +839,190 @@ fn main() {
 
     ncurses::endwin();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn linear_snake(cells: &[(u32, u32)]) -> std::collections::VecDeque<SnakePart> {
+        cells.iter().map(|&(x, y)| SnakePart{x, y}).collect()
+    }
+
+    fn test_state(snake: &[(u32, u32)], food: (u32, u32), walls: &[(u32, u32)], movement: Option<Movement>) -> SimState {
+        SimState {
+            width: 10,
+            height: 10,
+            snake: linear_snake(snake),
+            food,
+            walls: walls.to_vec(),
+            movement,
+            alive: true
+        }
+    }
+
+    #[test]
+    fn sim_apply_move_grows_on_food() {
+        let mut state = test_state(&[(4, 5), (5, 5)], (6, 5), &[], Some(Movement::Right));
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+
+        let reward = sim_apply_move(&mut state, Movement::Right, &mut rng);
+
+        assert!(state.alive);
+        assert_eq!(state.snake.len(), 3);
+        assert!(reward > 0.0);
+    }
+
+    #[test]
+    fn sim_apply_move_dies_on_wall() {
+        let mut state = test_state(&[(4, 5), (5, 5)], (0, 0), &[(6, 5)], Some(Movement::Right));
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+
+        let reward = sim_apply_move(&mut state, Movement::Right, &mut rng);
+
+        assert!(!state.alive);
+        assert_eq!(reward, MCTS_DEATH_PENALTY);
+    }
+
+    #[test]
+    fn sim_apply_move_dies_on_self_collision() {
+        // Reversing into the segment right behind the head (the "neck")
+        // should kill the snake even though the old tail was already popped.
+        let mut state = test_state(&[(5, 5), (5, 6), (5, 7)], (0, 0), &[], Some(Movement::Down));
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+
+        let reward = sim_apply_move(&mut state, Movement::Up, &mut rng);
+
+        assert!(!state.alive);
+        assert_eq!(reward, MCTS_DEATH_PENALTY);
+    }
+
+    #[test]
+    fn legal_moves_excludes_immediate_reversal() {
+        let state = test_state(&[(4, 5), (5, 5)], (9, 9), &[], Some(Movement::Right));
+        let moves = legal_moves(&state);
+        assert!(!moves.contains(&Movement::Left));
+    }
+
+    #[test]
+    fn legal_moves_allows_moving_into_vacating_tail() {
+        // The snake coils into a square so the head's left neighbor is the
+        // cell the tail currently occupies, which frees up this turn since
+        // the tail is popped before the new head lands.
+        let state = test_state(&[(5, 5), (5, 6), (6, 6), (6, 5)], (9, 9), &[], Some(Movement::Up));
+        let moves = legal_moves(&state);
+        assert!(moves.contains(&Movement::Left));
+    }
+
+    #[test]
+    fn flood_fill_reachable_excludes_sealed_pocket() {
+        let width = 8;
+        let height = 8;
+        let mut walls = classic_walls(width, height);
+
+        // Seal off (5, 5) behind a closed ring of walls.
+        for &(x, y) in &[(4, 4), (5, 4), (6, 4), (4, 5), (6, 5), (4, 6), (5, 6), (6, 6)] {
+            walls.push((x, y));
+        }
+
+        let reachable = flood_fill_reachable(&walls, width, height, (1, 1));
+
+        assert!(reachable.contains(&(2, 2)));
+        assert!(!reachable.contains(&(5, 5)));
+    }
+
+    #[test]
+    fn smooth_cave_fills_fully_enclosed_cell() {
+        let width = 5;
+        let height = 5;
+        let mut grid = vec![vec![true; width]; height];
+        grid[2][2] = false;
+
+        let next = smooth_cave(&grid, width as u32, height as u32);
+
+        // The center cell has 8 wall neighbors (>= the survival threshold),
+        // so it becomes a wall too.
+        assert!(next[2][2]);
+    }
+
+    #[test]
+    fn smooth_cave_preserves_open_cell_with_few_wall_neighbors() {
+        let width = 5;
+        let height = 5;
+        let grid = vec![vec![false; width]; height];
+
+        let next = smooth_cave(&grid, width as u32, height as u32);
+
+        assert!(!next[2][2]);
+    }
+
+    #[test]
+    fn smooth_cave_keeps_sparse_wall_cell_instead_of_eroding_it() {
+        let width = 5;
+        let height = 5;
+        let mut grid = vec![vec![false; width]; height];
+        grid[2][2] = true;
+        grid[2][3] = true;
+        grid[1][2] = true;
+
+        let next = smooth_cave(&grid, width as u32, height as u32);
+
+        // Below the birth/survival threshold, but an already-wall cell with
+        // a couple of wall neighbors still survives so smoothing doesn't
+        // erode thin walls into one large empty hall.
+        assert!(next[2][2]);
+    }
+
+    #[test]
+    fn cave_walls_keeps_border_solid_and_spawns_open() {
+        let width = 20;
+        let height = 15;
+        let spawn_a = (3, 3);
+        let spawn_b = (16, 11);
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+
+        let walls = cave_walls(width, height, &[spawn_a, spawn_b], &mut rng);
+        let wall_set: std::collections::HashSet<(u32, u32)> = walls.into_iter().collect();
+
+        for x in 0..width {
+            assert!(wall_set.contains(&(x, 0)));
+            assert!(wall_set.contains(&(x, height - 1)));
+        }
+        assert!(!wall_set.contains(&spawn_a));
+        assert!(!wall_set.contains(&spawn_b));
+    }
+
+    fn headless_game() -> Game {
+        let mut game = Game::new(raws::LevelDef::default_level());
+        game.entities.clear();
+        game
+    }
+
+    #[test]
+    fn step_turn_kills_both_snakes_on_equal_length_head_to_head() {
+        let mut game = headless_game();
+        game.snakes[0].body = linear_snake(&[(5, 5)]);
+        game.snakes[0].movement = Some(Movement::Right);
+        game.snakes[1].body = linear_snake(&[(7, 5)]);
+        game.snakes[1].movement = Some(Movement::Left);
+
+        game.step_turn();
+
+        assert!(!game.snakes[0].alive);
+        assert!(!game.snakes[1].alive);
+    }
+
+    #[test]
+    fn step_turn_only_kills_the_shorter_snake_on_head_to_head() {
+        let mut game = headless_game();
+        game.snakes[0].body = linear_snake(&[(5, 5)]);
+        game.snakes[0].movement = Some(Movement::Right);
+        game.snakes[1].body = linear_snake(&[(9, 5), (8, 5), (7, 5)]);
+        game.snakes[1].movement = Some(Movement::Left);
+
+        game.step_turn();
+
+        assert!(!game.snakes[0].alive);
+        assert!(game.snakes[1].alive);
+    }
+}
@@ -0,0 +1,381 @@
+use rand::{Rng, SeedableRng};
+
+use crate::{legal_moves, sim_apply_move, Movement, SimState, SnakePart};
+
+pub const DEFAULT_GENERATIONS: u32 = 200;
+pub const DEFAULT_WEIGHTS_PATH: &str = "snake_nn.weights";
+
+const NN_INPUT_SIZE: usize = 17;
+const NN_HIDDEN_SIZE: usize = 12;
+const NN_OUTPUT_SIZE: usize = 4;
+
+const NN_INPUT_WEIGHTS: usize = NN_HIDDEN_SIZE * NN_INPUT_SIZE;
+const NN_INPUT_BIAS: usize = NN_HIDDEN_SIZE;
+const NN_OUTPUT_WEIGHTS: usize = NN_OUTPUT_SIZE * NN_HIDDEN_SIZE;
+const NN_OUTPUT_BIAS: usize = NN_OUTPUT_SIZE;
+const NN_WEIGHT_COUNT: usize = NN_INPUT_WEIGHTS + NN_INPUT_BIAS + NN_OUTPUT_WEIGHTS + NN_OUTPUT_BIAS;
+
+const TRAIN_SEED: u64 = 0xC0FFEE;
+const TRAIN_MAX_STEPS: u32 = 400;
+const POPULATION_SIZE: usize = 60;
+const ELITE_COUNT: usize = 4;
+const TOURNAMENT_SIZE: usize = 5;
+const MUTATION_RATE: f64 = 0.1;
+const MUTATION_STRENGTH: f32 = 0.3;
+const FITNESS_FOOD_WEIGHT: f32 = 100.0;
+const FITNESS_SURVIVAL_WEIGHT: f32 = 0.1;
+
+// Small feed-forward network: 17 sensor inputs -> 12 hidden (tanh) -> 4
+// move scores, stored flat so crossover/mutation can walk it weight by
+// weight without caring about layer boundaries.
+#[derive(Clone)]
+pub struct Network {
+    weights: Vec<f32>
+}
+
+impl Network {
+    fn new_random(rng: &mut impl Rng) -> Self {
+        Self {
+            weights: (0..NN_WEIGHT_COUNT).map(|_| rng.gen_range(-1.0..1.0)).collect()
+        }
+    }
+
+    fn forward(&self, inputs: &[f32; NN_INPUT_SIZE]) -> [f32; NN_OUTPUT_SIZE] {
+        let input_weights = &self.weights[0..NN_INPUT_WEIGHTS];
+        let input_bias = &self.weights[NN_INPUT_WEIGHTS..NN_INPUT_WEIGHTS + NN_INPUT_BIAS];
+
+        let output_weights_offset = NN_INPUT_WEIGHTS + NN_INPUT_BIAS;
+        let output_weights = &self.weights[output_weights_offset..output_weights_offset + NN_OUTPUT_WEIGHTS];
+
+        let output_bias_offset = output_weights_offset + NN_OUTPUT_WEIGHTS;
+        let output_bias = &self.weights[output_bias_offset..output_bias_offset + NN_OUTPUT_BIAS];
+
+        let mut hidden = [0.0f32; NN_HIDDEN_SIZE];
+        for h in 0..NN_HIDDEN_SIZE {
+            let mut sum = input_bias[h];
+            for i in 0..NN_INPUT_SIZE {
+                sum += input_weights[h * NN_INPUT_SIZE + i] * inputs[i];
+            }
+            hidden[h] = sum.tanh();
+        }
+
+        let mut output = [0.0f32; NN_OUTPUT_SIZE];
+        for o in 0..NN_OUTPUT_SIZE {
+            let mut sum = output_bias[o];
+            for h in 0..NN_HIDDEN_SIZE {
+                sum += output_weights[o * NN_HIDDEN_SIZE + h] * hidden[h];
+            }
+            output[o] = sum;
+        }
+
+        output
+    }
+
+    // Scores the four moves and returns the highest-scoring one that isn't
+    // an immediate reversal/suicide, falling back to the raw best score if
+    // every direction is a dead end.
+    pub fn choose_move(&self, state: &SimState) -> Movement {
+        let output = self.forward(&sense(state));
+        let moves = [Movement::Up, Movement::Down, Movement::Left, Movement::Right];
+        let legal = legal_moves(state);
+
+        let mut best_move = moves[0];
+        let mut best_score = f32::MIN;
+
+        for (i, &mv) in moves.iter().enumerate() {
+            if !legal.is_empty() && !legal.contains(&mv) {
+                continue;
+            }
+            if output[i] > best_score {
+                best_score = output[i];
+                best_move = mv;
+            }
+        }
+
+        best_move
+    }
+
+    fn crossover(a: &Network, b: &Network, rng: &mut impl Rng) -> Network {
+        let weights = a.weights.iter().zip(b.weights.iter())
+            .map(|(&wa, &wb)| if rng.gen_bool(0.5) { wa } else { wb })
+            .collect();
+
+        Network{weights}
+    }
+
+    fn mutate(&mut self, rng: &mut impl Rng) {
+        for w in self.weights.iter_mut() {
+            if rng.gen_bool(MUTATION_RATE) {
+                *w += gaussian(rng, MUTATION_STRENGTH);
+            }
+        }
+    }
+
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        let text = self.weights.iter().map(|w| w.to_string()).collect::<Vec<_>>().join(" ");
+        std::fs::write(path, text)
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+        let weights: Vec<f32> = text.split_whitespace()
+            .map(|s| s.parse::<f32>().map_err(|_| format!("invalid weight value: {}", s)))
+            .collect::<Result<Vec<f32>, String>>()?;
+
+        if weights.len() != NN_WEIGHT_COUNT {
+            return Err(format!("expected {} weights, found {}", NN_WEIGHT_COUNT, weights.len()));
+        }
+
+        Ok(Self{weights})
+    }
+}
+
+// Box-Muller transform; avoids pulling in rand_distr for a single gaussian.
+fn gaussian(rng: &mut impl Rng, std_dev: f32) -> f32 {
+    let u1: f32 = rng.gen_range(f32::EPSILON..1.0);
+    let u2: f32 = rng.gen_range(0.0..1.0);
+    let z0 = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f32::consts::PI * u2).cos();
+    z0 * std_dev
+}
+
+// Casts a ray from the head in one direction, returning normalized
+// (wall, food, body) distances: 1/steps to the first hit, 0 if none.
+fn cast_ray(state: &SimState, head: (i64, i64), dir: (i64, i64)) -> (f32, f32, f32) {
+    let mut dist_wall = None;
+    let mut dist_food = None;
+    let mut dist_body = None;
+
+    let mut steps = 1i64;
+    let mut pos = (head.0 + dir.0, head.1 + dir.1);
+
+    while pos.0 >= 0 && pos.1 >= 0 && (pos.0 as u32) < state.width && (pos.1 as u32) < state.height {
+        let (x, y) = (pos.0 as u32, pos.1 as u32);
+
+        if dist_wall.is_none() && state.walls.iter().any(|&(wx, wy)| wx == x && wy == y) {
+            dist_wall = Some(steps);
+        }
+        if dist_food.is_none() && x == state.food.0 && y == state.food.1 {
+            dist_food = Some(steps);
+        }
+        if dist_body.is_none() && state.snake.iter().any(|p| p.x == x && p.y == y) {
+            dist_body = Some(steps);
+        }
+
+        steps += 1;
+        pos = (pos.0 + dir.0, pos.1 + dir.1);
+    }
+
+    let norm = |dist: Option<i64>| match dist {
+        Some(steps) => 1.0 / steps as f32,
+        None => 0.0
+    };
+
+    (norm(dist_wall), norm(dist_food), norm(dist_body))
+}
+
+fn sense(state: &SimState) -> [f32; NN_INPUT_SIZE] {
+    let head = *state.snake.back().unwrap();
+    let directions = [(0i64, -1i64), (0i64, 1i64), (-1i64, 0i64), (1i64, 0i64)];
+
+    let mut inputs = [0.0f32; NN_INPUT_SIZE];
+    let mut idx = 0;
+
+    for &dir in directions.iter() {
+        let (wall, food, body) = cast_ray(state, (head.x as i64, head.y as i64), dir);
+        inputs[idx] = wall;
+        inputs[idx + 1] = food;
+        inputs[idx + 2] = body;
+        idx += 3;
+    }
+
+    let movement_index = match state.movement {
+        Some(Movement::Up) => 0,
+        Some(Movement::Down) => 1,
+        Some(Movement::Left) => 2,
+        Some(Movement::Right) => 3,
+        None => 4
+    };
+    inputs[idx + movement_index] = 1.0;
+
+    inputs
+}
+
+// Same solid border as the classic `Game::init_field` arena, but with one
+// snake at the center and food anywhere in `2..width - 2` / `2..height - 2`,
+// built straight from a seeded RNG so training is reproducible and
+// ncurses-free.
+fn new_training_state(rng: &mut impl Rng) -> SimState {
+    let width = crate::DEFAULT_WIDTH;
+    let height = crate::DEFAULT_HEIGHT;
+
+    let mut walls = Vec::new();
+    for x in 0..width {
+        walls.push((x, 0));
+        walls.push((x, height - 1));
+    }
+    for y in 1..height - 1 {
+        walls.push((0, y));
+        walls.push((width - 1, y));
+    }
+
+    let mut snake = std::collections::VecDeque::new();
+    snake.push_back(SnakePart{x: width / 2, y: height / 2});
+
+    SimState {
+        width,
+        height,
+        snake,
+        food: (rng.gen_range(2..width - 2), rng.gen_range(2..height - 2)),
+        walls,
+        movement: None,
+        alive: true
+    }
+}
+
+// Plays one full silent game with `net` in control and scores it by food
+// eaten plus survival time.
+fn evaluate(net: &Network, seed: u64) -> f32 {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+    let mut state = new_training_state(&mut rng);
+    let start_len = state.snake.len();
+    let mut steps_survived = 0u32;
+
+    for _ in 0..TRAIN_MAX_STEPS {
+        if !state.alive {
+            break;
+        }
+
+        let mv = net.choose_move(&state);
+        sim_apply_move(&mut state, mv, &mut rng);
+        steps_survived += 1;
+    }
+
+    let food_eaten = (state.snake.len() - start_len) as f32;
+    food_eaten * FITNESS_FOOD_WEIGHT + steps_survived as f32 * FITNESS_SURVIVAL_WEIGHT
+}
+
+fn tournament_select(scored: &[(f32, usize)], rng: &mut impl Rng) -> usize {
+    (0..TOURNAMENT_SIZE)
+        .map(|_| scored[rng.gen_range(0..scored.len())])
+        .max_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+        .map(|(_, idx)| idx)
+        .unwrap()
+}
+
+// Genetic loop with double-buffered populations: `current` is evaluated and
+// bred into `next`, then the two buffers swap for the following epoch.
+pub fn train(generations: u32, out_path: &str) {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(TRAIN_SEED);
+
+    let mut current: Vec<Network> = (0..POPULATION_SIZE).map(|_| Network::new_random(&mut rng)).collect();
+    let mut next: Vec<Network> = Vec::with_capacity(POPULATION_SIZE);
+
+    let mut best = current[0].clone();
+    let mut best_fitness = f32::MIN;
+
+    for generation in 0..generations {
+        let seed = TRAIN_SEED.wrapping_add(generation as u64 + 1);
+
+        let mut scored: Vec<(f32, usize)> = current.iter()
+            .enumerate()
+            .map(|(i, net)| (evaluate(net, seed), i))
+            .collect();
+        scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+
+        if scored[0].0 > best_fitness {
+            best_fitness = scored[0].0;
+            best = current[scored[0].1].clone();
+        }
+
+        println!("generation {}: best fitness {:.2} (all-time {:.2})", generation, scored[0].0, best_fitness);
+
+        next.clear();
+        for &(_, idx) in scored.iter().take(ELITE_COUNT) {
+            next.push(current[idx].clone());
+        }
+        while next.len() < POPULATION_SIZE {
+            let parent_a = &current[tournament_select(&scored, &mut rng)];
+            let parent_b = &current[tournament_select(&scored, &mut rng)];
+            let mut child = Network::crossover(parent_a, parent_b, &mut rng);
+            child.mutate(&mut rng);
+            next.push(child);
+        }
+
+        std::mem::swap(&mut current, &mut next);
+    }
+
+    if let Err(err) = best.save(out_path) {
+        eprintln!("failed to save network weights to {}: {}", out_path, err);
+    } else {
+        println!("saved best network to {}", out_path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sense_reads_wall_food_and_direction_distances() {
+        let mut walls = Vec::new();
+        for x in 0..10 {
+            walls.push((x, 0));
+            walls.push((x, 9));
+        }
+        for y in 1..9 {
+            walls.push((0, y));
+            walls.push((9, y));
+        }
+
+        let mut snake = std::collections::VecDeque::new();
+        snake.push_back(SnakePart{x: 5, y: 5});
+
+        let state = SimState {
+            width: 10,
+            height: 10,
+            snake,
+            food: (5, 2),
+            walls,
+            movement: None,
+            alive: true
+        };
+
+        let inputs = sense(&state);
+
+        // Up: wall 5 steps away, food 3 steps away, no body in the ray.
+        assert!((inputs[0] - 1.0 / 5.0).abs() < 1e-6);
+        assert!((inputs[1] - 1.0 / 3.0).abs() < 1e-6);
+        assert_eq!(inputs[2], 0.0);
+
+        // Down: wall 4 steps away, no food, no body.
+        assert!((inputs[3] - 1.0 / 4.0).abs() < 1e-6);
+        assert_eq!(inputs[4], 0.0);
+
+        // No movement yet, so the "none" direction slot is set.
+        assert_eq!(inputs[12 + 4], 1.0);
+    }
+
+    #[test]
+    fn crossover_child_weights_come_from_either_parent() {
+        let a = Network { weights: vec![1.0; NN_WEIGHT_COUNT] };
+        let b = Network { weights: vec![2.0; NN_WEIGHT_COUNT] };
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+
+        let child = Network::crossover(&a, &b, &mut rng);
+
+        assert_eq!(child.weights.len(), NN_WEIGHT_COUNT);
+        assert!(child.weights.iter().all(|&w| w == 1.0 || w == 2.0));
+        assert!(child.weights.contains(&1.0));
+        assert!(child.weights.contains(&2.0));
+    }
+
+    #[test]
+    fn evaluate_returns_a_non_negative_finite_score() {
+        let net = Network { weights: vec![0.0; NN_WEIGHT_COUNT] };
+
+        let score = evaluate(&net, 7);
+
+        assert!(score.is_finite());
+        assert!(score >= 0.0);
+    }
+}
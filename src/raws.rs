@@ -0,0 +1,128 @@
+use serde::Deserialize;
+
+pub const DEFAULT_WALL_KIND: &str = "wall";
+pub const DEFAULT_FOOD_KIND: &str = "food";
+
+// `Game::init_field` places spawns at `width / 2 - 3` and `width / 2 + 3`,
+// and the autopilot simulation respawns food in `2..width - 2` (same for
+// height); reject anything smaller instead of panicking on a tiny level file.
+pub const MIN_LEVEL_WIDTH: u32 = 10;
+pub const MIN_LEVEL_HEIGHT: u32 = 10;
+
+// Per-entity-kind behavior, declared in a level file instead of hardcoded
+// in `render`/`update`. New hazards (poison, bonus food, portals, ...) only
+// need a new entry here, not a new Rust type or enum variant.
+#[derive(Deserialize, Clone, Default)]
+pub struct EntityDef {
+    pub glyph: char,
+    #[serde(default)]
+    pub kills: bool,
+    #[serde(default)]
+    pub grows: bool,
+    #[serde(default)]
+    pub consumable: bool,
+    #[serde(default)]
+    pub teleports: bool,
+    #[serde(default)]
+    pub score: i32
+}
+
+// A fixed instance of a declared `EntityDef` placed on the board, e.g. a
+// poison tile or a bonus-food pickup that isn't the single default food.
+#[derive(Deserialize, Clone)]
+pub struct EntityPlacement {
+    pub kind: String,
+    pub x: u32,
+    pub y: u32
+}
+
+#[derive(Deserialize, Clone)]
+pub struct LevelDef {
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub walls: Vec<(u32, u32)>,
+    pub entities: std::collections::HashMap<String, EntityDef>,
+    #[serde(default)]
+    pub entities_placed: Vec<EntityPlacement>,
+    #[serde(default = "default_wall_kind")]
+    pub wall_kind: String,
+    #[serde(default = "default_food_kind")]
+    pub food_kind: String
+}
+
+fn default_wall_kind() -> String {
+    DEFAULT_WALL_KIND.to_string()
+}
+
+fn default_food_kind() -> String {
+    DEFAULT_FOOD_KIND.to_string()
+}
+
+impl LevelDef {
+    fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+
+        let level: LevelDef = if path.ends_with(".json") {
+            serde_json::from_str(&text).map_err(|err| err.to_string())?
+        } else {
+            toml::from_str(&text).map_err(|err| err.to_string())?
+        };
+
+        if level.width < MIN_LEVEL_WIDTH || level.height < MIN_LEVEL_HEIGHT {
+            return Err(format!(
+                "level is {}x{}, below the minimum size of {}x{}",
+                level.width, level.height, MIN_LEVEL_WIDTH, MIN_LEVEL_HEIGHT
+            ));
+        }
+
+        Ok(level)
+    }
+
+    // Classic bordered arena with the built-in wall/food behaviors, used
+    // when no level file is given (or the given one fails to load).
+    pub fn default_level() -> Self {
+        let mut entities = std::collections::HashMap::new();
+        entities.insert(DEFAULT_WALL_KIND.to_string(), EntityDef{
+            glyph: '#',
+            kills: true,
+            grows: false,
+            consumable: false,
+            teleports: false,
+            score: 0
+        });
+        entities.insert(DEFAULT_FOOD_KIND.to_string(), EntityDef{
+            glyph: '@',
+            kills: false,
+            grows: true,
+            consumable: true,
+            teleports: false,
+            score: 1
+        });
+
+        Self {
+            width: crate::DEFAULT_WIDTH,
+            height: crate::DEFAULT_HEIGHT,
+            walls: Vec::new(),
+            entities,
+            entities_placed: Vec::new(),
+            wall_kind: default_wall_kind(),
+            food_kind: default_food_kind()
+        }
+    }
+}
+
+// Loads the level at `path`, falling back to `default_level` (and printing
+// why) if the file is missing, malformed, or too small to play on.
+pub fn load_level_or_default(path: Option<&str>) -> LevelDef {
+    match path {
+        Some(path) => match LevelDef::load(path) {
+            Ok(level) => level,
+            Err(err) => {
+                eprintln!("failed to load level {}: {}, using built-in default", path, err);
+                LevelDef::default_level()
+            }
+        },
+        None => LevelDef::default_level()
+    }
+}